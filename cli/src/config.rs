@@ -0,0 +1,98 @@
+use ga4gh_sdk::error::Ga4ghError;
+use ga4gh_sdk::utils::configuration::Configuration;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use url::Url;
+
+/// On-disk shape of the config file: a map of named service profiles.
+#[derive(Debug, Deserialize, Default)]
+struct FileConfig {
+    #[serde(default)]
+    profiles: HashMap<String, ProfileConfig>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+struct ProfileConfig {
+    base_path: Option<String>,
+    token: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+}
+
+fn default_config_path() -> PathBuf {
+    match std::env::var("HOME") {
+        Ok(home) => PathBuf::from(home).join(".config/ga4gh/config.toml"),
+        Err(_) => PathBuf::from(".config/ga4gh/config.toml"),
+    }
+}
+
+fn config_path(explicit: Option<&str>) -> PathBuf {
+    if let Some(path) = explicit {
+        return PathBuf::from(path);
+    }
+    if let Ok(path) = std::env::var("GA4GH_CONFIG") {
+        return PathBuf::from(path);
+    }
+    default_config_path()
+}
+
+fn read_file_config(path: &PathBuf) -> Result<FileConfig, Ga4ghError> {
+    if !path.exists() {
+        return Ok(FileConfig::default());
+    }
+    let contents = fs::read_to_string(path)
+        .map_err(|e| Ga4ghError::Config(format!("failed to read {}: {}", path.display(), e)))?;
+    toml::from_str(&contents)
+        .map_err(|e| Ga4ghError::Config(format!("failed to parse {}: {}", path.display(), e)))
+}
+
+/// Resolve the effective `Configuration` for a CLI invocation: load the named
+/// `profile` from the config file at `explicit_config_path` (falling back to
+/// `$GA4GH_CONFIG` then `~/.config/ga4gh/config.toml`), apply the credentials
+/// and headers it declares, then let an explicit `base_url` override the
+/// profile's `base_path`.
+pub fn load_configuration(
+    explicit_config_path: Option<&str>,
+    profile: Option<&str>,
+    base_url: Option<&str>,
+) -> Result<Configuration, Ga4ghError> {
+    let path = config_path(explicit_config_path);
+    let file_config = read_file_config(&path)?;
+
+    let profile_config = match profile {
+        Some(name) => file_config.profiles.get(name).cloned().ok_or_else(|| {
+            Ga4ghError::Config(format!("no profile named '{}' in {}", name, path.display()))
+        })?,
+        None => ProfileConfig::default(),
+    };
+
+    let base_path = base_url
+        .map(|s| s.to_string())
+        .or(profile_config.base_path)
+        .ok_or_else(|| {
+            Ga4ghError::Config(
+                "no base URL: pass --base-url or select a --profile with base_path set".into(),
+            )
+        })?;
+    let base_path = Url::parse(&base_path).map_err(|e| Ga4ghError::Config(e.to_string()))?;
+
+    let mut config = Configuration::default();
+    config.set_base_path(base_path);
+
+    if let Some(token) = profile_config.token {
+        config.set_bearer_token(token);
+    } else if let (Some(username), Some(password)) =
+        (profile_config.username, profile_config.password)
+    {
+        config.set_basic_auth(username, password);
+    }
+    for (key, value) in profile_config.headers {
+        config.set_header(key, value);
+    }
+
+    Ok(config)
+}
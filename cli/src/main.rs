@@ -1,17 +1,28 @@
-use clap::{arg, Command};
+mod config;
+mod output;
+mod service_info;
+mod submit;
+
+// `ga4gh_sdk` (the `Ga4ghError` enum, the `Result<_, Ga4ghError>` signatures on
+// `TES`/`Task`, `TesServiceInfo`, and `Configuration`'s auth setters) lives in
+// a separate crate that isn't part of this repository's tree: this checkout
+// only ever contained `cli/src/*`, with `ga4gh_sdk` consumed as an external
+// dependency already in the pre-existing baseline. Changes to that crate
+// belong in its own repository/series, not vendored in here.
+use clap::{arg, ArgMatches, Command};
 use ga4gh_sdk::clients::tes::models::ListTasksParams;
 use ga4gh_sdk::clients::tes::models::TesTask;
 use ga4gh_sdk::clients::tes::{Task, TES};
+use ga4gh_sdk::error::Ga4ghError;
 use ga4gh_sdk::utils::configuration::Configuration;
 use ga4gh_sdk::utils::test_utils::ensure_funnel_running;
 use ga4gh_sdk::utils::transport::Transport;
-use std::error::Error;
+use log::{debug, error};
+use output::OutputFormat;
 use std::fs;
 use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use url::Url;
-use log::{debug, error};
-
-use ga4gh_sdk::clients::tes::models::TesListTasksResponse;
 
 /// # Examples
 ///
@@ -70,56 +81,229 @@ use ga4gh_sdk::clients::tes::models::TesListTasksResponse;
 /// To run the `cancel` command:
 ///
 /// ```sh
-/// ga4gh-cli tes cancel cqgk5lj93m0311u6p530      
+/// ga4gh-cli tes cancel cqgk5lj93m0311u6p530
+/// ```
+///
+/// To run the `watch` command:
+///
+/// ```sh
+/// ga4gh-cli tes watch cqgk5lj93m0311u6p530 --interval 2 --max_interval 30
+/// ```
+///
+/// The `create`, `list`, `get` and `status` commands accept
+/// `--output table|wide|json|yaml` (`-o` for short) to control how results are
+/// rendered; `cancel`, `watch`, `submit` and `info` accept the flag but always
+/// print plain text. For example:
+///
+/// ```sh
+/// ga4gh-cli tes list --output json | jq '.tasks[].id'
+/// ```
+///
+/// To submit every `.tes` file in a directory with bounded concurrency:
+///
+/// ```sh
+/// ga4gh-cli tes submit ./tasks --concurrency 8 --shuffle --seed 42
+/// ```
+///
+/// To inspect the server's advertised capabilities:
+///
+/// ```sh
+/// ga4gh-cli tes info
 /// ```
-
 use ga4gh_sdk::clients::tes::models::TesState;
+use output::tes_state_to_str;
 
-fn tes_state_to_str(state: &Option<TesState>) -> &str {
-    match state {
-        Some(TesState::Unknown) => "Unknown",
-        Some(TesState::Queued) => "Queued",
-        Some(TesState::Initializing) => "Initializing",
-        Some(TesState::Running) => "Running",
-        Some(TesState::Paused) => "Paused",
-        Some(TesState::Complete) => "Complete",
-        Some(TesState::ExecutorError) => "Executor Error",
-        Some(TesState::SystemError) => "System Error",
-        Some(TesState::Canceled) => "Canceled",
-        Some(TesState::Canceling) => "Canceling",
-        Some(TesState::Preempted) => "Preempted",
-        None => "None",
-    }
+/// Wall-clock timestamp (seconds.millis since the Unix epoch) used to prefix
+/// each state-transition line printed by `tes watch`.
+fn watch_timestamp() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format!("{}.{:03}", now.as_secs(), now.subsec_millis())
+}
+
+fn is_terminal_state(state: &Option<TesState>) -> bool {
+    matches!(
+        state,
+        Some(TesState::Complete)
+            | Some(TesState::ExecutorError)
+            | Some(TesState::SystemError)
+            | Some(TesState::Canceled)
+            | Some(TesState::Preempted)
+    )
 }
 
-fn format_task(task: &TesTask) -> String {
-    format!(
-        "{:<25} {:<15}\n",
-        task.id.as_deref().unwrap_or("None"),
-        tes_state_to_str(&task.state)
+fn is_error_state(state: &Option<TesState>) -> bool {
+    matches!(
+        state,
+        Some(TesState::ExecutorError) | Some(TesState::SystemError)
     )
 }
 
-fn format_tasks_response(response: &TesListTasksResponse) -> String {
-    let mut table = String::new();
-    let headers = format!("{:<25} {:<15}\n", "TASK ID", "State");
-    table.push_str(&headers);
-    for task in &response.tasks {
-        table.push_str(&format_task(task));
+/// Map a `Ga4ghError` to a process exit code so scripts can branch on failure
+/// class without parsing the error message.
+fn exit_code_for(err: &Ga4ghError) -> i32 {
+    match err {
+        Ga4ghError::Transport { .. } => 2,
+        Ga4ghError::Json(_) => 3,
+        Ga4ghError::Config(_) => 4,
+        Ga4ghError::Tes(_) => 5,
+        _ => 1,
+    }
+}
+
+fn fail(context: &str, err: Ga4ghError) -> ! {
+    eprintln!("{}: {}", context, err);
+    std::process::exit(exit_code_for(&err));
+}
+
+/// Parse a CLI argument string into `T`, mapping a failure to a
+/// `Ga4ghError::Config` naming the flag instead of panicking.
+fn parse_arg<T: std::str::FromStr>(value: &str, flag: &str) -> Result<T, Ga4ghError>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| Ga4ghError::Config(format!("invalid --{}: {}", flag, e)))
+}
+
+/// Resolve the `Configuration` for a subcommand invocation from the global
+/// `--config`/`--profile`/`--base-url` flags, falling back to auto-starting a
+/// local Funnel instance when none of them were supplied.
+async fn resolve_configuration(matches: &ArgMatches) -> Result<Configuration, Ga4ghError> {
+    let config_path = matches.value_of("config");
+    let profile = matches.value_of("profile");
+    let base_url = matches.value_of("base_url");
+
+    if config_path.is_some() || profile.is_some() || base_url.is_some() {
+        return config::load_configuration(config_path, profile, base_url);
+    }
+
+    let mut config = Configuration::default();
+    let funnel_url = ensure_funnel_running().await;
+    let funnel_url = Url::parse(&funnel_url).map_err(|e| Ga4ghError::Config(e.to_string()))?;
+    config.set_base_path(funnel_url);
+    Ok(config)
+}
+
+/// Resolve the `--output`/`-o` flag into an `OutputFormat`, rejecting an
+/// unrecognized value instead of silently falling back to `table`.
+fn resolve_output_format(matches: &ArgMatches) -> Result<OutputFormat, Ga4ghError> {
+    let value = matches.value_of("output").unwrap_or("table");
+    OutputFormat::parse(value).ok_or_else(|| {
+        Ga4ghError::Config(format!(
+            "invalid --output '{}': expected table, wide, json, or yaml",
+            value
+        ))
+    })
+}
+
+/// How `watch_task` stopped polling.
+enum WatchOutcome {
+    /// The task reached a terminal `TesState`.
+    Terminal(Option<TesState>),
+    /// `--timeout` elapsed before the task reached a terminal state.
+    TimedOut(Option<TesState>),
+    /// The watch was interrupted (Ctrl-C) before the task reached a terminal state.
+    Interrupted(Option<TesState>),
+}
+
+/// Poll `task.status()` on an interval until it reaches a terminal `TesState`,
+/// re-printing a timestamped line each time the observed state changes.
+///
+/// The poll interval starts at `interval` and doubles after every poll that
+/// doesn't produce a state change, capped at `max_interval`; it resets back to
+/// `interval` as soon as the state changes. `timeout`, if set, is raced
+/// against each poll interval directly so it fires as soon as it elapses
+/// rather than after whatever backoff happens to be in flight.
+async fn watch_task(
+    task: &Task,
+    interval: Duration,
+    max_interval: Duration,
+    timeout: Option<Duration>,
+    cancel_on_exit: bool,
+) -> Result<WatchOutcome, Ga4ghError> {
+    let start = tokio::time::Instant::now();
+    let mut backoff = interval;
+    let mut last_state: Option<TesState> = None;
+
+    loop {
+        let timed_out = async {
+            match timeout {
+                Some(timeout) => tokio::time::sleep(timeout.saturating_sub(start.elapsed())).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => {}
+            _ = timed_out => {
+                println!(
+                    "[{}] watch timed out after {:?}",
+                    watch_timestamp(),
+                    timeout.unwrap()
+                );
+                return Ok(WatchOutcome::TimedOut(last_state));
+            }
+            ctrl_c = tokio::signal::ctrl_c() => {
+                ctrl_c.map_err(|e| Ga4ghError::Config(e.to_string()))?;
+                println!("[{}] watch interrupted", watch_timestamp());
+                if cancel_on_exit {
+                    println!("[{}] cancelling task", watch_timestamp());
+                    task.cancel().await?;
+                }
+                return Ok(WatchOutcome::Interrupted(last_state));
+            }
+        }
+
+        let status = task.status().await?;
+        if status != last_state {
+            println!(
+                "[{}] {} -> {}",
+                watch_timestamp(),
+                tes_state_to_str(&last_state),
+                tes_state_to_str(&status)
+            );
+            last_state = status.clone();
+            backoff = interval;
+        } else {
+            backoff = std::cmp::min(backoff * 2, max_interval);
+        }
+
+        if is_terminal_state(&last_state) {
+            return Ok(WatchOutcome::Terminal(last_state));
+        }
     }
-    table
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
+async fn main() {
     env_logger::init();
 
+    if let Err(e) = run().await {
+        fail("Error", e);
+    }
+}
+
+/// Build the CLI, dispatch to the selected subcommand, and return any error
+/// it produces so `main` can route it through `fail`/`exit_code_for` instead
+/// of relying on the default `Result` exit behaviour.
+async fn run() -> Result<(), Ga4ghError> {
     let cmd = Command::new("cli")
         .bin_name("cli")
         .version("0.1.0")
         .about("CLI to manage tasks")
         .subcommand_required(true)
         .arg_required_else_help(true)
+        .arg(arg!(--config [PATH] "Path to the ga4gh config file").global(true))
+        .arg(arg!(--profile [NAME] "Named service profile to use from the config file").global(true))
+        .arg(arg!(--base_url [URL] "Override the service base URL").global(true))
+        .arg(
+            arg!(-o --output [FORMAT] "Output format: table, wide, json, or yaml")
+                .default_value("table")
+                .global(true),
+        )
         .subcommand(
             Command::new("tes")
                 .about("TES subcommands")
@@ -161,6 +345,30 @@ async fn main() -> Result<(), Box<dyn Error>> {
                         .about("cancel the task")
                         .arg(arg!(<id> "The id of the task which should be cancel"))
                         .arg_required_else_help(true),
+                )
+                .subcommand(
+                    Command::new("watch")
+                        .about("poll a task until it reaches a terminal state")
+                        .arg(arg!(<id> "The id of the task which should be watched"))
+                        .arg(arg!(--interval [SECONDS] "Initial poll interval in seconds").default_value("2"))
+                        .arg(arg!(--max_interval [SECONDS] "Maximum poll interval in seconds").default_value("30"))
+                        .arg(arg!(--timeout [SECONDS] "Abort watching after this many seconds"))
+                        .arg(arg!(--cancel_on_exit "Cancel the task if the watch is interrupted"))
+                        .arg_required_else_help(true),
+                )
+                .subcommand(
+                    Command::new("submit")
+                        .about("submit a directory or manifest of .tes files concurrently")
+                        .arg(arg!(<INPUT> "A directory of .tes files, or a newline-delimited manifest of paths"))
+                        .arg(arg!(--concurrency [N] "Maximum number of submissions in flight at once").default_value("4"))
+                        .arg(arg!(--shuffle "Randomize submission order"))
+                        .arg(arg!(--seed [SEED] "Seed for --shuffle, for reproducible load-testing runs").default_value("0"))
+                        .arg(arg!(--fail_fast "Abort remaining submissions on the first error"))
+                        .arg_required_else_help(true),
+                )
+                .subcommand(
+                    Command::new("info")
+                        .about("query the server's /service-info endpoint"),
                 ),
         );
 
@@ -182,38 +390,42 @@ async fn main() -> Result<(), Box<dyn Error>> {
                         task_file.to_string()
                     }
                 };
-                let testask: TesTask = serde_json::from_str(&task_json)
-                    .map_err(|e| format!("Failed to parse JSON: {}", e))?;
-                let mut config = Configuration::default();
-                // let mut config = load_configuration();
-                let funnel_url = ensure_funnel_running().await;
-                let funnel_url = url::Url::parse(&funnel_url).expect("Invalid URL");
-                config.set_base_path(funnel_url);
+                let testask: TesTask = serde_json::from_str(&task_json)?;
+                let config = resolve_configuration(sub).await?;
+                let format = resolve_output_format(sub)?;
                 match TES::new(&config).await {
                     Ok(tes) => {
-                        let task = tes.create(testask).await;
-                        println!("{:?}", task);
-                    }
-                    Err(e) => {
-                        error!("Error creating TES instance: {:?}", e);
-                        return Err(e);
+                        if let Ok(info) = tes.service_info().await {
+                            service_info::warn_unsupported_storage(&info, &testask);
+                        }
+                        match tes.create(testask).await {
+                            Ok(task) => println!("{}", output::render_task(&task, format)),
+                            Err(e) => fail("Error creating task", e),
+                        }
                     }
+                    Err(e) => fail("Error creating TES instance", e),
                 };
             }
             if let Some(("list", sub)) = sub.subcommand() {
                 debug!("list subcommand");
                 let name_prefix = sub.value_of("name_prefix").map(|s| s.to_string());
-                let state = sub.value_of("state").map(|s| serde_json::from_str(s).expect("Invalid state"));
+                let state = sub
+                    .value_of("state")
+                    .map(serde_json::from_str)
+                    .transpose()?;
                 let _tag_key = sub.value_of("tag_key").map(|s| s.to_string());
                 let _tag_value = sub.value_of("tag_value").map(|s| s.to_string());
-                let page_size = sub.value_of("page_size").map(|s| s.parse().expect("Invalid page_size"));
+                let page_size = sub
+                    .value_of("page_size")
+                    .map(|s| parse_arg(s, "page_size"))
+                    .transpose()?;
                 let page_token = sub.value_of("page_token").map(|s| s.to_string());
                 let view = sub.value_of("view").map(|s| s.to_string());
 
                 let parameters = ListTasksParams {
                     name_prefix,
                     state,
-                    tag_key: None, // Example does not cover parsing Vec<String>
+                    tag_key: None,   // Example does not cover parsing Vec<String>
                     tag_value: None, // Example does not cover parsing Vec<String>
                     page_size,
                     page_token,
@@ -221,88 +433,142 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 };
 
                 debug!("parameters are: {:?}", parameters);
-                let mut config = Configuration::default();
-                
-                let funnel_url = ensure_funnel_running().await;
-                let funnel_url = url::Url::parse(&funnel_url).expect("Invalid URL");
-                config.set_base_path(funnel_url);
-                
+                let config = resolve_configuration(sub).await?;
+                let format = resolve_output_format(sub)?;
+
                 match TES::new(&config).await {
-                    Ok(tes) => {
-                        match tes.list_tasks(Some(parameters)).await {
-                            Ok(task_response) => {
-                                println!("{}", format_tasks_response(&task_response)); 
-                            },
-                            Err(e) => {
-                                eprintln!("Error listing tasks: {}", e);
-                            }
+                    Ok(tes) => match tes.list_tasks(Some(parameters)).await {
+                        Ok(task_response) => {
+                            println!("{}", output::render_tasks_response(&task_response, format));
                         }
+                        Err(e) => fail("Error listing tasks", e),
                     },
-                    Err(e) => {
-                        error!("Error creating TES instance: {:?}", e);
-                        return Err(e);
-                    }
+                    Err(e) => fail("Error creating TES instance", e),
                 };
             }
             if let Some(("get", sub)) = sub.subcommand() {
-                let mut config = Configuration::default();
                 let id = sub.value_of("id").unwrap();
                 let view = sub.value_of("view").unwrap();
 
-                // let mut config = load_configuration();
-                let funnel_url = ensure_funnel_running().await;
-                let funnel_url = url::Url::parse(&funnel_url).expect("Invalid URL");
-                config.set_base_path(funnel_url);
+                let config = resolve_configuration(sub).await?;
+                let format = resolve_output_format(sub)?;
                 match TES::new(&config).await {
-                    Ok(tes) => {
-                        let task = tes.get(view, id).await;
-                        println!("{:?}", task);
-                    }
-                    Err(e) => {
-                        error!("Error creating TES instance: {:?}", e);
-                        return Err(e);
-                    }
+                    Ok(tes) => match tes.get(view, id).await {
+                        Ok(task) => println!("{}", output::render_task(&task, format)),
+                        Err(e) => fail("Error getting task", e),
+                    },
+                    Err(e) => fail("Error creating TES instance", e),
                 };
             }
             if let Some(("status", sub)) = sub.subcommand() {
-                let mut config = Configuration::default();
                 let id = sub.value_of("id").unwrap().to_string();
 
-                // let mut config = load_configuration();
-                let funnel_url = ensure_funnel_running().await;
-                let funnel_url = url::Url::parse(&funnel_url).expect("Invalid URL");
-                config.set_base_path(funnel_url);
+                let config = resolve_configuration(sub).await?;
+                let format = resolve_output_format(sub)?;
                 let transport = Transport::new(&config);
                 let task = Task::new(id.clone(), transport);
                 match task.status().await {
-                    Ok(status) => {
-                        println!("TASKID: {}", id.clone());
-                        println!("STATUS: {:?}", status);
-                    }
-                    Err(e) => {
-                        error!("Error creating Task instance: {:?}", e);
-                        return Err(e);
-                    }
+                    Ok(status) => println!("{}", output::render_status(&id, &status, format)),
+                    Err(e) => fail("Error creating Task instance", e),
                 };
             }
             if let Some(("cancel", sub)) = sub.subcommand() {
-                let mut config = Configuration::default();
                 let id = sub.value_of("id").unwrap().to_string();
 
-                // let mut config = load_configuration();
-                let funnel_url = ensure_funnel_running().await;
-                let funnel_url = Url::parse(&funnel_url).expect("Invalid URL");
-                config.set_base_path(funnel_url);
+                let config = resolve_configuration(sub).await?;
                 let transport = Transport::new(&config);
                 let task = Task::new(id, transport);
                 match task.cancel().await {
                     Ok(output) => {
                         println!("The new value is: {:?}", output);
                     }
-                    Err(e) => {
-                        error!("Error creating Task instance: {:?}", e);
-                        return Err(e);
+                    Err(e) => fail("Error creating Task instance", e),
+                };
+            }
+            if let Some(("watch", sub)) = sub.subcommand() {
+                let id = sub.value_of("id").unwrap().to_string();
+                let interval: u64 = parse_arg(sub.value_of("interval").unwrap(), "interval")?;
+                let max_interval: u64 =
+                    parse_arg(sub.value_of("max_interval").unwrap(), "max_interval")?;
+                let timeout = sub
+                    .value_of("timeout")
+                    .map(|s| parse_arg::<u64>(s, "timeout"))
+                    .transpose()?
+                    .map(Duration::from_secs);
+                let cancel_on_exit = sub.is_present("cancel_on_exit");
+
+                let config = resolve_configuration(sub).await?;
+                let transport = Transport::new(&config);
+                let task = Task::new(id.clone(), transport);
+
+                match watch_task(
+                    &task,
+                    Duration::from_secs(interval),
+                    Duration::from_secs(max_interval),
+                    timeout,
+                    cancel_on_exit,
+                )
+                .await
+                {
+                    Ok(WatchOutcome::Terminal(final_state)) => {
+                        if is_error_state(&final_state) {
+                            eprintln!(
+                                "Task {} finished in state {}",
+                                id,
+                                tes_state_to_str(&final_state)
+                            );
+                            std::process::exit(1);
+                        }
+                    }
+                    Ok(WatchOutcome::TimedOut(_)) | Ok(WatchOutcome::Interrupted(_)) => {
+                        // The task never reached a terminal state, so this isn't a
+                        // success: use a distinct code from the error-state exit above
+                        // so scripts can tell "failed" apart from "didn't finish".
+                        std::process::exit(6);
+                    }
+                    Err(e) => fail("Error watching task", e),
+                }
+            }
+            if let Some(("submit", sub)) = sub.subcommand() {
+                let input = sub.value_of("INPUT").unwrap();
+                let concurrency: usize =
+                    parse_arg(sub.value_of("concurrency").unwrap(), "concurrency")?;
+                let shuffle = sub.is_present("shuffle");
+                let seed: u64 = parse_arg(sub.value_of("seed").unwrap(), "seed")?;
+                let fail_fast = sub.is_present("fail_fast");
+
+                let files = submit::collect_task_files(input)?;
+                let config = resolve_configuration(sub).await?;
+
+                match TES::new(&config).await {
+                    Ok(tes) => {
+                        let outcomes = submit::submit_all(
+                            &tes,
+                            files,
+                            submit::SubmitOptions {
+                                concurrency,
+                                shuffle,
+                                seed,
+                                fail_fast,
+                            },
+                        )
+                        .await;
+                        println!("{}", submit::format_summary(&outcomes));
+                        if outcomes.iter().any(|outcome| outcome.result.is_err()) {
+                            std::process::exit(1);
+                        }
                     }
+                    Err(e) => fail("Error creating TES instance", e),
+                };
+            }
+            if let Some(("info", sub)) = sub.subcommand() {
+                let config = resolve_configuration(sub).await?;
+                match TES::new(&config).await {
+                    Ok(tes) => match tes.service_info().await {
+                        Ok(info) => println!("{}", service_info::format_service_info(&info)),
+                        Err(e) => fail("Error fetching service info", e),
+                    },
+                    Err(e) => fail("Error creating TES instance", e),
                 };
             }
         }
@@ -312,4 +578,4 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
     }
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,159 @@
+use ga4gh_sdk::clients::tes::models::{TesListTasksResponse, TesState, TesTask};
+use serde::Serialize;
+
+/// Output format selected via the global `--output`/`-o` flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Wide,
+    Json,
+    Yaml,
+}
+
+impl OutputFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "table" => Some(Self::Table),
+            "wide" => Some(Self::Wide),
+            "json" => Some(Self::Json),
+            "yaml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    fn is_structured(self) -> bool {
+        matches!(self, Self::Json | Self::Yaml)
+    }
+}
+
+pub fn tes_state_to_str(state: &Option<TesState>) -> &str {
+    match state {
+        Some(TesState::Unknown) => "Unknown",
+        Some(TesState::Queued) => "Queued",
+        Some(TesState::Initializing) => "Initializing",
+        Some(TesState::Running) => "Running",
+        Some(TesState::Paused) => "Paused",
+        Some(TesState::Complete) => "Complete",
+        Some(TesState::ExecutorError) => "Executor Error",
+        Some(TesState::SystemError) => "System Error",
+        Some(TesState::Canceled) => "Canceled",
+        Some(TesState::Canceling) => "Canceling",
+        Some(TesState::Preempted) => "Preempted",
+        None => "None",
+    }
+}
+
+fn serialize<T: Serialize>(value: &T, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_string_pretty(value).unwrap_or_else(|e| format!("<json error: {}>", e))
+        }
+        OutputFormat::Yaml => {
+            serde_yaml::to_string(value).unwrap_or_else(|e| format!("<yaml error: {}>", e))
+        }
+        OutputFormat::Table | OutputFormat::Wide => {
+            unreachable!("table formats are rendered by the caller")
+        }
+    }
+}
+
+fn id_width<'a>(ids: impl Iterator<Item = &'a str>) -> usize {
+    ids.map(str::len).max().unwrap_or(0).max("TASK ID".len())
+}
+
+fn task_name(task: &TesTask) -> &str {
+    task.name.as_deref().unwrap_or("None")
+}
+
+fn task_creation_time(task: &TesTask) -> &str {
+    task.creation_time.as_deref().unwrap_or("None")
+}
+
+fn task_image(task: &TesTask) -> &str {
+    task.executors
+        .first()
+        .map(|executor| executor.image.as_str())
+        .unwrap_or("None")
+}
+
+fn format_task_row(task: &TesTask, id_width: usize, wide: bool) -> String {
+    let id = task.id.as_deref().unwrap_or("None");
+    let state = tes_state_to_str(&task.state);
+    if wide {
+        format!(
+            "{:id_width$} {:<15} {:<25} {:<25} {:<25}\n",
+            id,
+            state,
+            task_name(task),
+            task_creation_time(task),
+            task_image(task),
+            id_width = id_width,
+        )
+    } else {
+        format!("{:id_width$} {:<15}\n", id, state, id_width = id_width)
+    }
+}
+
+fn table_header(id_width: usize, wide: bool) -> String {
+    if wide {
+        format!(
+            "{:id_width$} {:<15} {:<25} {:<25} {:<25}\n",
+            "TASK ID",
+            "STATE",
+            "NAME",
+            "CREATED",
+            "IMAGE",
+            id_width = id_width,
+        )
+    } else {
+        format!(
+            "{:id_width$} {:<15}\n",
+            "TASK ID",
+            "STATE",
+            id_width = id_width
+        )
+    }
+}
+
+pub fn render_task(task: &TesTask, format: OutputFormat) -> String {
+    if format.is_structured() {
+        return serialize(task, format);
+    }
+    let wide = format == OutputFormat::Wide;
+    let width = id_width(std::iter::once(task.id.as_deref().unwrap_or("None")));
+    let mut table = table_header(width, wide);
+    table.push_str(&format_task_row(task, width, wide));
+    table
+}
+
+#[derive(Serialize)]
+struct TaskStatus<'a> {
+    id: &'a str,
+    state: &'a Option<TesState>,
+}
+
+pub fn render_status(id: &str, state: &Option<TesState>, format: OutputFormat) -> String {
+    if format.is_structured() {
+        let status = TaskStatus { id, state };
+        return serialize(&status, format);
+    }
+    format!("TASKID: {}\nSTATUS: {}\n", id, tes_state_to_str(state))
+}
+
+pub fn render_tasks_response(response: &TesListTasksResponse, format: OutputFormat) -> String {
+    if format.is_structured() {
+        return serialize(response, format);
+    }
+    let wide = format == OutputFormat::Wide;
+    let width = id_width(
+        response
+            .tasks
+            .iter()
+            .map(|task| task.id.as_deref().unwrap_or("None")),
+    );
+    let mut table = table_header(width, wide);
+    for task in &response.tasks {
+        table.push_str(&format_task_row(task, width, wide));
+    }
+    table
+}
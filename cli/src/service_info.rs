@@ -0,0 +1,60 @@
+use ga4gh_sdk::clients::tes::models::{TesServiceInfo, TesTask};
+use url::Url;
+
+/// Render a human-readable summary of a TES server's advertised capabilities.
+pub fn format_service_info(info: &TesServiceInfo) -> String {
+    let storage = info.storage.clone().unwrap_or_default();
+    let mut out = format!(
+        "Name:    {}\nVersion: {}\n",
+        info.name.as_deref().unwrap_or("None"),
+        info.version.as_deref().unwrap_or("None"),
+    );
+    if storage.is_empty() {
+        out.push_str("Storage: none advertised\n");
+    } else {
+        out.push_str(&format!("Storage: {}\n", storage.join(", ")));
+    }
+    out
+}
+
+/// Extract the `scheme` of every input/output URL referenced by a task, e.g.
+/// `s3` from `s3://bucket/key`.
+fn referenced_schemes(task: &TesTask) -> Vec<String> {
+    task.inputs
+        .iter()
+        .flatten()
+        .filter_map(|input| input.url.clone())
+        .chain(
+            task.outputs
+                .iter()
+                .flatten()
+                .filter_map(|output| output.url.clone()),
+        )
+        .filter_map(|url| Url::parse(&url).ok().map(|url| url.scheme().to_string()))
+        .collect()
+}
+
+/// Warn about any storage scheme a task references that the server's
+/// `/service-info` response didn't advertise support for, so users get fast
+/// local feedback instead of an opaque server rejection. Prints nothing when
+/// the server advertises no storage at all.
+pub fn warn_unsupported_storage(info: &TesServiceInfo, task: &TesTask) {
+    let supported = info.storage.clone().unwrap_or_default();
+    if supported.is_empty() {
+        return;
+    }
+    let advertised_schemes: Vec<String> = supported
+        .iter()
+        .filter_map(|s| Url::parse(s).ok().map(|u| u.scheme().to_string()))
+        .collect();
+    for scheme in referenced_schemes(task) {
+        let advertised = advertised_schemes.iter().any(|s| *s == scheme);
+        if !advertised {
+            eprintln!(
+                "warning: task references '{}://' storage, which the server did not advertise (advertised: {})",
+                scheme,
+                supported.join(", ")
+            );
+        }
+    }
+}
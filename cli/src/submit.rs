@@ -0,0 +1,133 @@
+use futures::stream::{self, StreamExt};
+use ga4gh_sdk::clients::tes::models::TesTask;
+use ga4gh_sdk::clients::tes::TES;
+use ga4gh_sdk::error::Ga4ghError;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub struct SubmitOptions {
+    pub concurrency: usize,
+    pub shuffle: bool,
+    pub seed: u64,
+    pub fail_fast: bool,
+}
+
+pub struct SubmissionOutcome {
+    pub file: PathBuf,
+    pub result: Result<String, String>,
+}
+
+/// Collect the `.tes` task files to submit from `input`: every `*.tes` file in
+/// a directory, the file itself if `input` is a single `.tes` file, or
+/// otherwise the newline-delimited list of paths in a manifest file.
+pub fn collect_task_files(input: &str) -> Result<Vec<PathBuf>, Ga4ghError> {
+    let path = Path::new(input);
+    if !path.exists() {
+        return Err(Ga4ghError::Config(format!(
+            "no such file or directory: {}",
+            input
+        )));
+    }
+
+    if path.is_dir() {
+        let mut files: Vec<PathBuf> = fs::read_dir(path)
+            .map_err(|e| Ga4ghError::Config(e.to_string()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().map(|ext| ext == "tes").unwrap_or(false))
+            .collect();
+        files.sort();
+        return Ok(files);
+    }
+
+    if path.extension().map(|ext| ext == "tes").unwrap_or(false) {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let contents = fs::read_to_string(path).map_err(|e| Ga4ghError::Config(e.to_string()))?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+fn shuffled(mut files: Vec<PathBuf>, seed: u64) -> Vec<PathBuf> {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    files.shuffle(&mut rng);
+    files
+}
+
+async fn submit_one(tes: &TES, file: &Path) -> Result<String, String> {
+    let contents = fs::read_to_string(file).map_err(|e| e.to_string())?;
+    let task: TesTask = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+    tes.create(task)
+        .await
+        .map(|created| created.id.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// Submit every file in `files` against `tes`, keeping at most
+/// `options.concurrency` `tes.create` calls in flight at once. Stops polling
+/// for further results as soon as a submission fails when `options.fail_fast`
+/// is set; otherwise every file is attempted and reported.
+pub async fn submit_all(
+    tes: &TES,
+    files: Vec<PathBuf>,
+    options: SubmitOptions,
+) -> Vec<SubmissionOutcome> {
+    let files = if options.shuffle {
+        shuffled(files, options.seed)
+    } else {
+        files
+    };
+
+    let mut submissions = stream::iter(files.into_iter().map(|file| async move {
+        let result = submit_one(tes, &file).await;
+        SubmissionOutcome { file, result }
+    }))
+    .buffer_unordered(options.concurrency.max(1));
+
+    let mut outcomes = Vec::new();
+    while let Some(outcome) = submissions.next().await {
+        let failed = outcome.result.is_err();
+        outcomes.push(outcome);
+        if failed && options.fail_fast {
+            break;
+        }
+    }
+    outcomes
+}
+
+/// Render a filename -> task id/error summary table for a batch of
+/// submissions, sorted by filename so a fixed `--seed` always produces the
+/// same report regardless of completion order.
+pub fn format_summary(outcomes: &[SubmissionOutcome]) -> String {
+    let mut outcomes: Vec<&SubmissionOutcome> = outcomes.iter().collect();
+    outcomes.sort_by(|a, b| a.file.cmp(&b.file));
+
+    let width = outcomes
+        .iter()
+        .map(|outcome| outcome.file.display().to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max("FILE".len());
+
+    let mut table = format!("{:width$} {}\n", "FILE", "RESULT", width = width);
+    for outcome in outcomes {
+        let result = match &outcome.result {
+            Ok(id) => format!("ok: {}", id),
+            Err(e) => format!("error: {}", e),
+        };
+        table.push_str(&format!(
+            "{:width$} {}\n",
+            outcome.file.display(),
+            result,
+            width = width
+        ));
+    }
+    table
+}